@@ -1,19 +1,35 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use axum::{
-    extract::State,
+    extract::{Path, State},
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
     http::StatusCode,
     Json,
     response::IntoResponse,
-    Router, routing::get,
+    response::sse::{Event, KeepAlive, Sse},
+    Router, routing::{get, post},
+};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, RwLock};
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
 };
-use serde::Deserialize;
-use tokio::sync::RwLock;
 
 #[tokio::main]
 async fn main() {
-    let global_price = Arc::new(RwLock::new(None));
-    let app = app(global_price);
+    let global_price = Arc::new(PriceState::new());
+    let app = app(
+        global_price,
+        AppConfig::default()
+            .with_compression(true)
+            .with_compression_min_size(256),
+    );
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
         .await
@@ -22,167 +38,799 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
-fn app(state: GlobalPrice) -> Router {
-    Router::new()
-        .route("/price", get(get_price).patch(set_price).delete(set_null_price))
-        .with_state(state)
+/// Knobs for building the router that aren't part of the price-store state itself.
+#[derive(Debug, Clone, Default)]
+struct AppConfig {
+    compression: bool,
+    compression_min_size: Option<u64>,
+}
+
+impl AppConfig {
+    fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    fn with_compression_min_size(mut self, min_size: u64) -> Self {
+        self.compression_min_size = Some(min_size);
+        self
+    }
+}
+
+fn app(state: GlobalPrice, config: AppConfig) -> Router {
+    let router = Router::new()
+        .route("/price/:symbol", get(get_price).patch(set_price).delete(set_null_price))
+        .route("/price/:symbol/history", get(get_price_history))
+        .route("/prices", get(get_all_prices))
+        .route("/price/stream", get(stream_price))
+        .route("/price/ws", get(price_ws))
+        .route("/rpc", post(rpc))
+        .with_state(state);
+
+    if config.compression {
+        let size_above = match config.compression_min_size {
+            Some(min_size) => SizeAbove::new(min_size),
+            None => SizeAbove::default(),
+        };
+        // Keep the library's exclusions (gRPC, images, SSE) alongside our configured
+        // size threshold, rather than replacing them outright.
+        let predicate = size_above
+            .and(NotForContentType::GRPC)
+            .and(NotForContentType::IMAGES)
+            .and(NotForContentType::SSE);
+        router.layer(CompressionLayer::new().compress_when(predicate))
+    } else {
+        router
+    }
 }
 
 async fn get_price(
+    Path(symbol): Path<String>,
     State(global_price): State<GlobalPrice>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let global_price = global_price.read().await;
-    if let Some(price) = *global_price {
+    if let Some(price) = read_price(&global_price, &symbol).await {
         Ok(price.to_string())
     } else {
         Err(StatusCode::NOT_FOUND)
     }
 }
 
+async fn get_all_prices(State(global_price): State<GlobalPrice>) -> impl IntoResponse {
+    let prices = global_price.prices.read().await.clone();
+    Json(prices)
+}
+
+async fn get_price_history(
+    Path(symbol): Path<String>,
+    State(global_price): State<GlobalPrice>,
+) -> impl IntoResponse {
+    let history = global_price.history.read().await;
+    Json(history.get(&symbol).cloned().unwrap_or_default())
+}
+
+async fn read_price(global_price: &GlobalPrice, symbol: &str) -> Option<u64> {
+    global_price.prices.read().await.get(symbol).copied()
+}
+
+async fn write_price(global_price: &GlobalPrice, symbol: &str, price: Option<u64>) {
+    {
+        let mut prices = global_price.prices.write().await;
+        match price {
+            Some(price) => {
+                prices.insert(symbol.to_string(), price);
+            }
+            None => {
+                prices.remove(symbol);
+            }
+        }
+    }
+
+    if let Some(price) = price {
+        let entry = HistoryEntry {
+            price,
+            timestamp: current_timestamp_millis(),
+        };
+        let mut history = global_price.history.write().await;
+        history.entry(symbol.to_string()).or_default().push(entry);
+    }
+
+    let _ = global_price.tx.send(PriceEvent {
+        symbol: symbol.to_string(),
+        price,
+    });
+}
+
+fn current_timestamp_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A past price observed for a symbol, recorded on every write.
+#[derive(Debug, Clone, Serialize)]
+struct HistoryEntry {
+    price: u64,
+    timestamp: u64,
+}
+
+/// Broadcast to subscribers (SSE, WebSocket) whenever a symbol's price changes.
+#[derive(Debug, Clone, Serialize)]
+struct PriceEvent {
+    symbol: String,
+    price: Option<u64>,
+}
+
+async fn stream_price(
+    State(global_price): State<GlobalPrice>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut rx = global_price.tx.subscribe();
+
+    let stream = async_stream::stream! {
+        loop {
+            match rx.recv().await {
+                Ok(event) => yield Ok(Event::default().json_data(&event).unwrap()),
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    // We missed some updates; re-read the current state of every symbol
+                    // and replay it so the subscriber resyncs instead of staying stale.
+                    let snapshot = global_price.prices.read().await.clone();
+                    for (symbol, price) in snapshot {
+                        let event = PriceEvent { symbol, price: Some(price) };
+                        yield Ok(Event::default().json_data(&event).unwrap());
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+/// Inbound control frame toggling whether the socket receives price updates.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum WsControl {
+    Subscribe,
+    Unsubscribe,
+}
+
+async fn price_ws(
+    ws: WebSocketUpgrade,
+    State(global_price): State<GlobalPrice>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_price_ws(socket, global_price))
+}
+
+async fn handle_price_ws(mut socket: WebSocket, global_price: GlobalPrice) {
+    let mut rx = global_price.tx.subscribe();
+    let mut subscribed = false;
+
+    loop {
+        tokio::select! {
+            inbound = socket.recv() => {
+                match inbound {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Ok(control) = serde_json::from_str::<WsControl>(&text) {
+                            subscribed = matches!(control, WsControl::Subscribe);
+                        }
+                    }
+                    Some(Ok(Message::Ping(payload))) => {
+                        if socket.send(Message::Pong(payload)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) => break,
+                }
+            }
+            update = rx.recv() => {
+                if !subscribed {
+                    continue;
+                }
+                let event = match update {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let frame = serde_json::to_string(&json!({ "price": event.price })).unwrap();
+                if socket.send(Message::Text(frame)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct PriceDto {
     price: u64,
 }
 
 async fn set_price(
+    Path(symbol): Path<String>,
     State(global_price): State<GlobalPrice>,
     Json(input): Json<PriceDto>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let price = input.price;
-    let mut global_price = global_price.write().await;
-    *global_price = Some(price);
+    write_price(&global_price, &symbol, Some(input.price)).await;
 
     Ok(StatusCode::OK)
 }
 
 async fn set_null_price(
+    Path(symbol): Path<String>,
     State(global_price): State<GlobalPrice>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let mut global_price = global_price.write().await;
-    *global_price = None;
+    write_price(&global_price, &symbol, None).await;
 
     Ok(StatusCode::OK)
 }
 
-type GlobalPrice = Arc<RwLock<Option<u64>>>;
+/// A JSON-RPC 2.0 request, per https://www.jsonrpc.org/specification.
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    id: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcSymbolParams {
+    symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcSetParams {
+    symbol: String,
+    price: u64,
+}
+
+const RPC_PARSE_ERROR: i64 = -32700;
+const RPC_INVALID_REQUEST: i64 = -32600;
+const RPC_METHOD_NOT_FOUND: i64 = -32601;
+const RPC_INVALID_PARAMS: i64 = -32602;
+const RPC_PRICE_NOT_SET: i64 = -32000;
+
+async fn rpc(State(global_price): State<GlobalPrice>, body: axum::body::Bytes) -> Json<Value> {
+    let body: Value = match serde_json::from_slice(&body) {
+        Ok(body) => body,
+        Err(_) => return Json(rpc_error(Value::Null, RPC_PARSE_ERROR, "Parse error")),
+    };
+
+    let response = match body {
+        Value::Array(calls) => {
+            let mut responses = Vec::with_capacity(calls.len());
+            for call in calls {
+                responses.push(dispatch_rpc_call(&global_price, call).await);
+            }
+            Value::Array(responses)
+        }
+        call => dispatch_rpc_call(&global_price, call).await,
+    };
+
+    Json(response)
+}
+
+async fn dispatch_rpc_call(global_price: &GlobalPrice, call: Value) -> Value {
+    let request: RpcRequest = match serde_json::from_value(call) {
+        Ok(request) => request,
+        Err(_) => return rpc_error(Value::Null, RPC_INVALID_REQUEST, "Invalid Request"),
+    };
+
+    if request.jsonrpc != "2.0" {
+        return rpc_error(request.id, RPC_INVALID_REQUEST, "Invalid Request");
+    }
+
+    match request.method.as_str() {
+        "price_get" => match serde_json::from_value::<RpcSymbolParams>(request.params) {
+            Ok(params) => match read_price(global_price, &params.symbol).await {
+                Some(price) => rpc_success(request.id, json!(price)),
+                None => rpc_error(request.id, RPC_PRICE_NOT_SET, "Price not set"),
+            },
+            Err(_) => rpc_error(request.id, RPC_INVALID_PARAMS, "Invalid params"),
+        },
+        "price_set" => match serde_json::from_value::<RpcSetParams>(request.params) {
+            Ok(params) => {
+                write_price(global_price, &params.symbol, Some(params.price)).await;
+                rpc_success(request.id, Value::Null)
+            }
+            Err(_) => rpc_error(request.id, RPC_INVALID_PARAMS, "Invalid params"),
+        },
+        "price_delete" => match serde_json::from_value::<RpcSymbolParams>(request.params) {
+            Ok(params) => {
+                write_price(global_price, &params.symbol, None).await;
+                rpc_success(request.id, Value::Null)
+            }
+            Err(_) => rpc_error(request.id, RPC_INVALID_PARAMS, "Invalid params"),
+        },
+        _ => rpc_error(request.id, RPC_METHOD_NOT_FOUND, "Method not found"),
+    }
+}
+
+fn rpc_success(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "result": result, "id": id })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": id })
+}
+
+/// Holds the per-symbol prices and history alongside a broadcast channel so
+/// subscribers (SSE, WebSocket, ...) can be notified of updates without polling.
+struct PriceState {
+    prices: RwLock<HashMap<String, u64>>,
+    history: RwLock<HashMap<String, Vec<HistoryEntry>>>,
+    tx: broadcast::Sender<PriceEvent>,
+}
+
+impl PriceState {
+    fn new() -> Self {
+        Self::with_prices(HashMap::new())
+    }
+
+    fn with_prices(prices: HashMap<String, u64>) -> Self {
+        let (tx, _rx) = broadcast::channel(16);
+        Self {
+            prices: RwLock::new(prices),
+            history: RwLock::new(HashMap::new()),
+            tx,
+        }
+    }
+}
+
+type GlobalPrice = Arc<PriceState>;
 
 #[cfg(test)]
 mod tests {
     use axum::{
         body::Body,
-        http::{self, Request, StatusCode},
+        http::{self, HeaderName, Request, StatusCode},
     };
     use axum::body::Bytes;
     use axum::response::Response;
     use axum::routing::RouterIntoService;
     use http_body_util::BodyExt;
+    use serde::de::DeserializeOwned;
     use serde_json::{json, Value};
     use tower::{Service, ServiceExt};
 
     use super::*;
 
+    /// Drives a [`Router`] in-process via [`tower::Service`], without binding a socket.
+    struct TestClient {
+        service: RouterIntoService<Body>,
+    }
+
+    impl TestClient {
+        fn new(router: Router) -> Self {
+            Self {
+                service: router.into_service(),
+            }
+        }
+
+        async fn get(&mut self, uri: &str) -> TestResponse {
+            self.send(http::Method::GET, uri, &[], None).await
+        }
+
+        async fn patch(&mut self, uri: &str, json: &Value) -> TestResponse {
+            self.send(http::Method::PATCH, uri, &[], Some(json)).await
+        }
+
+        async fn delete(&mut self, uri: &str) -> TestResponse {
+            self.send(http::Method::DELETE, uri, &[], None).await
+        }
+
+        async fn post(&mut self, uri: &str, json: &Value) -> TestResponse {
+            self.send(http::Method::POST, uri, &[], Some(json)).await
+        }
+
+        async fn post_raw(&mut self, uri: &str, body: &str) -> TestResponse {
+            let request = Request::builder()
+                .method(http::Method::POST)
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .uri(uri)
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            self.call(request).await
+        }
+
+        async fn get_with_header(&mut self, uri: &str, header: (HeaderName, &str)) -> TestResponse {
+            self.send(http::Method::GET, uri, &[header], None).await
+        }
+
+        async fn send(
+            &mut self,
+            method: http::Method,
+            uri: &str,
+            extra_headers: &[(HeaderName, &str)],
+            maybe_json: Option<&Value>,
+        ) -> TestResponse {
+            let body = match maybe_json {
+                Some(json) => Body::from(serde_json::to_vec(json).unwrap()),
+                None => Body::empty(),
+            };
+
+            let mut builder = Request::builder()
+                .method(method)
+                .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .uri(uri);
+            for (name, value) in extra_headers {
+                builder = builder.header(name, *value);
+            }
+
+            self.call(builder.body(body).unwrap()).await
+        }
+
+        async fn call(&mut self, request: Request<Body>) -> TestResponse {
+            let response = ServiceExt::<Request<Body>>::ready(&mut self.service)
+                .await
+                .unwrap()
+                .call(request)
+                .await
+                .unwrap();
+            TestResponse { response }
+        }
+    }
+
+    /// Wraps an HTTP response with ergonomic, test-friendly accessors.
+    struct TestResponse {
+        response: Response<Body>,
+    }
+
+    impl TestResponse {
+        fn status(&self) -> StatusCode {
+            self.response.status()
+        }
+
+        fn header(&self, name: HeaderName) -> Option<&str> {
+            self.response.headers().get(name)?.to_str().ok()
+        }
+
+        async fn bytes(self) -> Bytes {
+            self.response.into_body().collect().await.unwrap().to_bytes()
+        }
+
+        async fn text(self) -> String {
+            String::from_utf8(self.bytes().await.to_vec()).unwrap()
+        }
+
+        async fn json<T: DeserializeOwned>(self) -> T {
+            serde_json::from_slice(&self.bytes().await).unwrap()
+        }
+    }
+
+    /// Binds the app to a real ephemeral TCP socket, for tests that need an actual
+    /// connection (e.g. streaming endpoints) rather than the in-process `tower::Service` path.
+    struct LiveTestClient {
+        addr: std::net::SocketAddr,
+    }
+
+    impl LiveTestClient {
+        async fn bind(router: Router) -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                axum::serve(listener, router).await.unwrap();
+            });
+            Self { addr }
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("http://{}{}", self.addr, path)
+        }
+    }
+
+    fn state_with(prices: &[(&str, u64)]) -> GlobalPrice {
+        let prices = prices
+            .iter()
+            .map(|(symbol, price)| (symbol.to_string(), *price))
+            .collect();
+        Arc::new(PriceState::with_prices(prices))
+    }
+
     #[tokio::test]
     async fn get_price_test() {
-        let state = Arc::new(RwLock::new(Some(100)));
-        let mut app = app(state).into_service();
+        let state = state_with(&[("BTC", 100)]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
 
-        let request = build_request(
-            http::Method::GET,
-            "/price",
-            None
-        );
-        let response = call(request, &mut app).await;
+        let response = client.get("/price/BTC").await;
         assert_eq!(response.status(), StatusCode::OK);
-        assert_eq!(collect_body(response).await, "100");
+        assert_eq!(response.text().await, "100");
     }
 
     #[tokio::test]
     async fn get_not_found_price_test() {
-        let state = Arc::new(RwLock::new(None));
-        let mut app = app(state).into_service();
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
 
-        let request = build_request(
-            http::Method::GET,
-            "/price",
-            None
-        );
-        let response = call(request, &mut app).await;
+        let response = client.get("/price/BTC").await;
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
-        assert_eq!(collect_body(response).await, "");
+        assert_eq!(response.text().await, "");
     }
 
     #[tokio::test]
     async fn patch_price_test() {
-        let state = Arc::new(RwLock::new(None));
-        let mut app = app(state).into_service();
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
 
-        let request = build_request(
-            http::Method::PATCH,
-            "/price",
-            Some(&json!({"price": 355}))
-        );
-        let response = call(request, &mut app).await;
+        let response = client.patch("/price/BTC", &json!({"price": 355})).await;
         assert_eq!(response.status(), StatusCode::OK);
 
-        let request = build_request(
-            http::Method::GET,
-            "/price",
-            None
-        );
-        let response = call(request, &mut app).await;
+        let response = client.get("/price/BTC").await;
         assert_eq!(response.status(), StatusCode::OK);
-        assert_eq!(collect_body(response).await, "355");
+        assert_eq!(response.text().await, "355");
     }
 
     #[tokio::test]
     async fn delete_price_test() {
-        let state = Arc::new(RwLock::new(Some(5)));
-        let mut app = app(state).into_service();
+        let state = state_with(&[("BTC", 5)]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
 
-        let request = build_request(
-            http::Method::DELETE,
-            "/price",
-            None
-        );
-        let response = call(request, &mut app).await;
+        let response = client.delete("/price/BTC").await;
         assert_eq!(response.status(), StatusCode::OK);
 
-        let request = build_request(
-            http::Method::GET,
-            "/price",
-            None
-        );
-        let response = call(request, &mut app).await;
+        let response = client.get("/price/BTC").await;
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
-        assert_eq!(collect_body(response).await, "");
+        assert_eq!(response.text().await, "");
     }
 
-    fn build_request(method: http::Method, uri: &str, maybe_json: Option<&Value>) -> Request<Body> {
-        let body = match maybe_json {
-            Some(json) => Body::from(
-                serde_json::to_vec(json).unwrap(),
-            ),
-            None => Body::empty(),
-        };
+    #[tokio::test]
+    async fn distinct_symbols_do_not_interfere_test() {
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
 
-        Request::builder()
-            .method(method)
-            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
-            .uri(uri)
-            .body(body)
-            .unwrap()
+        client.patch("/price/BTC", &json!({"price": 100})).await;
+        client.patch("/price/ETH", &json!({"price": 10})).await;
+
+        let response = client.get("/prices").await;
+        assert_eq!(response.json::<Value>().await, json!({"BTC": 100, "ETH": 10}));
     }
 
-    async fn call(request: Request<Body>, app: &mut RouterIntoService<Body>) -> Response<Body> {
-        ServiceExt::<Request<Body>>::ready(app)
-            .await
-            .unwrap()
-            .call(request)
-            .await
+    #[tokio::test]
+    async fn concurrent_writes_to_distinct_symbols_test() {
+        let state = state_with(&[]);
+        let app = app(state, AppConfig::default()).into_service();
+
+        let symbols = ["BTC", "ETH", "SOL", "DOGE"];
+        let mut handles = Vec::new();
+        for (i, symbol) in symbols.iter().enumerate() {
+            let mut app = app.clone();
+            let symbol = symbol.to_string();
+            handles.push(tokio::spawn(async move {
+                let request = Request::builder()
+                    .method(http::Method::PATCH)
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .uri(format!("/price/{symbol}"))
+                    .body(Body::from(serde_json::to_vec(&json!({"price": i as u64})).unwrap()))
+                    .unwrap();
+                ServiceExt::<Request<Body>>::ready(&mut app)
+                    .await
+                    .unwrap()
+                    .call(request)
+                    .await
+                    .unwrap();
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let mut client = TestClient { service: app };
+        let response = client.get("/prices").await;
+        let body: Value = response.json().await;
+        for (i, symbol) in symbols.iter().enumerate() {
+            assert_eq!(body[symbol], json!(i as u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn price_history_is_recorded_per_symbol_test() {
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
+
+        for price in [100, 110, 105] {
+            client.patch("/price/BTC", &json!({"price": price})).await;
+        }
+
+        let response = client.get("/price/BTC/history").await;
+        let body: Value = response.json().await;
+        let prices: Vec<u64> = body
+            .as_array()
             .unwrap()
+            .iter()
+            .map(|entry| entry["price"].as_u64().unwrap())
+            .collect();
+        assert_eq!(prices, vec![100, 110, 105]);
     }
 
-    async fn collect_body(response: Response<Body>) -> Bytes {
-        response.into_body().collect().await.unwrap().to_bytes()
+    #[tokio::test]
+    async fn rpc_get_and_set_price_test() {
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
+
+        let response = client
+            .post(
+                "/rpc",
+                &json!({"jsonrpc": "2.0", "method": "price_set", "params": {"symbol": "BTC", "price": 42}, "id": 1}),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json::<Value>().await, json!({"jsonrpc": "2.0", "result": null, "id": 1}));
+
+        let response = client
+            .post(
+                "/rpc",
+                &json!({"jsonrpc": "2.0", "method": "price_get", "params": {"symbol": "BTC"}, "id": 2}),
+            )
+            .await;
+        assert_eq!(response.json::<Value>().await, json!({"jsonrpc": "2.0", "result": 42, "id": 2}));
+    }
+
+    #[tokio::test]
+    async fn rpc_get_missing_price_is_application_error_test() {
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
+
+        let response = client
+            .post(
+                "/rpc",
+                &json!({"jsonrpc": "2.0", "method": "price_get", "params": {"symbol": "BTC"}, "id": 1}),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = response.json().await;
+        assert_eq!(body["error"]["code"], json!(-32000));
+    }
+
+    #[tokio::test]
+    async fn rpc_unknown_method_test() {
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
+
+        let response = client
+            .post("/rpc", &json!({"jsonrpc": "2.0", "method": "price_frobnicate", "id": 1}))
+            .await;
+        let body: Value = response.json().await;
+        assert_eq!(body["error"]["code"], json!(-32601));
+    }
+
+    #[tokio::test]
+    async fn rpc_batch_request_test() {
+        let state = state_with(&[("BTC", 7)]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
+
+        let response = client
+            .post(
+                "/rpc",
+                &json!([
+                    {"jsonrpc": "2.0", "method": "price_get", "params": {"symbol": "BTC"}, "id": 1},
+                    {"jsonrpc": "2.0", "method": "price_delete", "params": {"symbol": "BTC"}, "id": 2},
+                ]),
+            )
+            .await;
+        let body: Value = response.json().await;
+        assert_eq!(body, json!([
+            {"jsonrpc": "2.0", "result": 7, "id": 1},
+            {"jsonrpc": "2.0", "result": null, "id": 2},
+        ]));
+    }
+
+    #[tokio::test]
+    async fn rpc_parse_error_test() {
+        let state = state_with(&[]);
+        let mut client = TestClient::new(app(state, AppConfig::default()));
+
+        let response = client.post_raw("/rpc", "not json").await;
+        let body: Value = response.json().await;
+        assert_eq!(body["error"]["code"], json!(-32700));
+    }
+
+    #[tokio::test]
+    async fn compression_negotiates_br_for_large_response_test() {
+        let prices = (0..50)
+            .map(|i| (format!("SYMBOL_{i}"), i))
+            .collect::<HashMap<_, _>>();
+        let state = Arc::new(PriceState::with_prices(prices));
+        let config = AppConfig::default().with_compression(true);
+        let mut client = TestClient::new(app(state, config));
+
+        let response = client
+            .get_with_header("/prices", (http::header::ACCEPT_ENCODING, "br"))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.header(http::header::CONTENT_ENCODING).unwrap(), "br");
+    }
+
+    #[tokio::test]
+    async fn compression_skips_small_response_below_threshold_test() {
+        let state = state_with(&[("BTC", 5)]);
+        let config = AppConfig::default().with_compression(true);
+        let mut client = TestClient::new(app(state, config));
+
+        let response = client
+            .get_with_header("/price/BTC", (http::header::ACCEPT_ENCODING, "br"))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.header(http::header::CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn compression_honors_custom_min_size_threshold_test() {
+        let state = state_with(&[("BTC", 5)]);
+        let config = AppConfig::default()
+            .with_compression(true)
+            .with_compression_min_size(0);
+        let mut client = TestClient::new(app(state, config));
+
+        let response = client
+            .get_with_header("/price/BTC", (http::header::ACCEPT_ENCODING, "br"))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.header(http::header::CONTENT_ENCODING).unwrap(), "br");
+    }
+
+    #[tokio::test]
+    async fn compression_never_wraps_the_sse_stream_test() {
+        let state = state_with(&[]);
+        let config = AppConfig::default()
+            .with_compression(true)
+            .with_compression_min_size(0);
+        let mut client = TestClient::new(app(state, config));
+
+        let response = client
+            .get_with_header("/price/stream", (http::header::ACCEPT_ENCODING, "br"))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.header(http::header::CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn price_stream_delivers_updates_over_a_real_socket_test() {
+        let state = state_with(&[]);
+        let server = LiveTestClient::bind(app(state.clone(), AppConfig::default())).await;
+        assert!(server.url("/price/stream").contains(&server.addr.to_string()));
+
+        let mut stream = tokio::net::TcpStream::connect(server.addr).await.unwrap();
+        let request = format!(
+            "GET /price/stream HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            server.addr
+        );
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        // The SSE handler subscribes to the broadcast channel only after the
+        // request is dispatched, so keep publishing until a reader is attached.
+        let repeat_state = state.clone();
+        let setter = tokio::spawn(async move {
+            loop {
+                write_price(&repeat_state, "BTC", Some(42)).await;
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+
+        let received = tokio::time::timeout(Duration::from_secs(2), async {
+            let mut buf = [0u8; 1024];
+            let mut received = String::new();
+            while !received.contains("\"BTC\"") {
+                let n = stream.read(&mut buf).await.unwrap();
+                assert!(n > 0, "socket closed before an SSE event was received");
+                received.push_str(&String::from_utf8_lossy(&buf[..n]));
+            }
+            received
+        })
+        .await
+        .expect("timed out waiting for an SSE event");
+        setter.abort();
+
+        assert!(received.contains("\"symbol\":\"BTC\""));
+        assert!(received.contains("\"price\":42"));
     }
 }